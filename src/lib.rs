@@ -1,7 +1,11 @@
 use std::env;
 use std::collections::HashMap;
+use std::fmt;
+#[cfg(any(feature = "config_json", feature = "config_toml"))]
+use std::fs;
+use std::path::Path;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum CmdOptionValueType {
     String,
     Number,
@@ -9,6 +13,66 @@ pub enum CmdOptionValueType {
     NoValue,
 }
 
+impl fmt::Display for CmdOptionValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CmdOptionValueType::String => write!(f, "string"),
+            CmdOptionValueType::Number => write!(f, "number"),
+            CmdOptionValueType::Float => write!(f, "float"),
+            CmdOptionValueType::NoValue => write!(f, "no parameter"),
+        }
+    }
+}
+
+///
+/// Errors that can occur while parsing command line arguments against the registered options
+#[derive(Debug)]
+pub enum CmdError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue { option: String, expected: CmdOptionValueType, got: String, allowed: Option<Vec<String>> },
+    MissingRequired(Vec<String>),
+}
+
+impl fmt::Display for CmdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CmdError::UnknownOption(opt) => write!(f, "unknown option: {}", opt),
+            CmdError::MissingValue(opt) => write!(f, "option '{}' requires a value", opt),
+            CmdError::InvalidValue { option, expected, got, allowed: None } =>
+                write!(f, "option '{}' expected {}, got '{}'", option, expected, got),
+            CmdError::InvalidValue { option, expected, got, allowed: Some(values) } =>
+                write!(f, "option '{}' expected {} ({}), got '{}'", option, expected, values.join("|"), got),
+            CmdError::MissingRequired(opts) => write!(f, "missing required option(s): {}", opts.join(", ")),
+        }
+    }
+}
+
+///
+/// Errors that can occur while loading and merging a config file
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String),
+    Parse(String),
+    UnsupportedFormat(String),
+    TypeMismatch { option: String, expected: CmdOptionValueType, got: String },
+    InvalidValue { option: String, allowed: Vec<String>, got: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(msg) => write!(f, "failed to read config file: {}", msg),
+            ConfigError::Parse(msg) => write!(f, "failed to parse config file: {}", msg),
+            ConfigError::UnsupportedFormat(ext) => write!(f, "unsupported config file format: {}", ext),
+            ConfigError::TypeMismatch { option, expected, got } =>
+                write!(f, "config value for '{}' expected {}, got {}", option, expected, got),
+            ConfigError::InvalidValue { option, allowed, got } =>
+                write!(f, "config value for '{}' must be one of {}, got '{}'", option, allowed.join("|"), got),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum CmdArgumentValue {
     String(String),
@@ -29,11 +93,17 @@ struct CmdLineOption<'a> {
     longform: &'a str,
     description: &'a str,
     value_type: CmdOptionValueType,
+    required: bool,
+    allowed: Option<&'a [&'a str]>,
 }
 
 pub struct Commander<'a> {
     options: Vec<CmdLineOption<'a>>,
     args: HashMap<String, CmdArgument>,
+    config: HashMap<String, CmdArgumentValue>,
+    free: Vec<String>,
+    subcommands: Vec<(&'a str, &'a str, Commander<'a>)>,
+    active_subcommand: Option<String>,
 }
 
 /*
@@ -42,6 +112,12 @@ pub struct Commander<'a> {
     - Write a method to retrieve an option's value (if it exists) (otherwise None)
 */
 
+impl<'a> Default for Commander<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Commander<'a> {
     ///
     /// Create's a new instance of the Commander struct
@@ -49,46 +125,116 @@ impl<'a> Commander<'a> {
         Commander {
             options: Vec::new(),
             args: HashMap::new(),
+            config: HashMap::new(),
+            free: Vec::new(),
+            subcommands: Vec::new(),
+            active_subcommand: None,
         }
     }
 
     ///
     /// You should call this method as the last call as part of initialisation of supported options.
-    /// 
+    /// This only parses the command line; it does not check `add_required_option` options by
+    /// itself, so that a required option can still be satisfied by a `load_config` call made
+    /// afterwards. Call `validate_required()` once parsing and any config loading are done.
+    ///
     /// # Examples
     /// ```
     /// use commander::{Commander, CmdOptionValueType};
-    /// 
+    ///
     /// let mut cmd = Commander::new();
     /// cmd.add_option("v", "version", "Show the version of this application", CmdOptionValueType::NoValue)
     ///     .add_option("h", "help", "Show this help", CmdOptionValueType::NoValue)
-    ///     .add_option("if", "input", "File to use as input", CmdOptionValueType::String)
-    ///     .init();
+    ///     .add_option("if", "input", "File to use as input", CmdOptionValueType::String);
+    /// cmd.init().expect("failed to parse command line arguments");
+    /// cmd.validate_required().expect("missing required option(s)");
     /// ```
-    pub fn init(&mut self) {
+    pub fn init(&mut self) -> Result<(), CmdError> {
         let args = env::args().collect::<Vec<String>>();
 
         self.add_executable_arg(&args);
-        self.parse_args(args);
+        self.parse_args(args)
     }
 
     ///
     /// Add a supported option. All added options will be checked for when the Commander finally initialises with
     /// the provided command line arguments
     pub fn add_option(&mut self, shortform: &'a str, longform: &'a str, description: &'a str, value_type: CmdOptionValueType) -> &mut Self{
-        let option = CmdLineOption {
+        self.push_option(CmdLineOption {
             shortform,
             longform,
             description,
-            value_type
-        };
+            value_type,
+            required: false,
+            allowed: None,
+        })
+    }
+
+    ///
+    /// Add a String option whose value is restricted to one of `allowed`. Behaves like
+    /// `add_option` with `CmdOptionValueType::String`, but `parse_args` rejects any value
+    /// outside that list with `CmdError::InvalidValue`, and `help()` renders the choices
+    /// inline, e.g. `[string: json|toml|text]`.
+    pub fn add_option_with_values(&mut self, shortform: &'a str, longform: &'a str, description: &'a str, allowed: &'a [&'a str]) -> &mut Self {
+        self.push_option(CmdLineOption {
+            shortform,
+            longform,
+            description,
+            value_type: CmdOptionValueType::String,
+            required: false,
+            allowed: Some(allowed),
+        })
+    }
+
+    ///
+    /// Add a supported option that must be supplied, either on the command line or via a loaded
+    /// config file. Behaves like `add_option`, but requires a call to `validate_required()` once
+    /// parsing and any config loading are done, which reports `CmdError::MissingRequired` listing
+    /// every such option that is still absent at that point.
+    pub fn add_required_option(&mut self, shortform: &'a str, longform: &'a str, description: &'a str, value_type: CmdOptionValueType) -> &mut Self {
+        self.push_option(CmdLineOption {
+            shortform,
+            longform,
+            description,
+            value_type,
+            required: true,
+            allowed: None,
+        })
+    }
 
+    // PRIVATE
+    fn push_option(&mut self, option: CmdLineOption<'a>) -> &mut Self {
         self.options.push(option);
-        self.options.sort_by(|a, b| a.shortform.cmp(&b.shortform) );
+        self.options.sort_by(|a, b| a.shortform.cmp(b.shortform));
 
         self
     }
 
+    ///
+    /// Registers a named subcommand with its own option table, returning a mutable reference to
+    /// the nested `Commander` so its options are declared the same way as top-level ones, e.g.
+    /// `cmd.add_subcommand("add", "Add file contents to the index").add_option(...)`.
+    /// If the first non-flag argument on the command line matches `name`, parsing is handed off
+    /// entirely to this nested Commander.
+    pub fn add_subcommand(&mut self, name: &'a str, description: &'a str) -> &mut Commander<'a> {
+        self.subcommands.push((name, description, Commander::new()));
+        &mut self.subcommands.last_mut().unwrap().2
+    }
+
+    ///
+    /// Returns the name of the subcommand that was matched during parsing, if any.
+    pub fn active_subcommand(&self) -> Option<&str> {
+        self.active_subcommand.as_deref()
+    }
+
+    // PRIVATE
+    // The Commander that should actually answer getter queries: the active subcommand if one
+    // was matched during parsing, otherwise this Commander itself has no delegate.
+    fn resolved_subcommand(&self) -> Option<&Commander<'a>> {
+        let name = self.active_subcommand.as_deref()?;
+        self.subcommands.iter().find(|(n, _, _)| *n == name).map(|(_, _, c)| c)
+    }
+
     ///
     /// Returns the number of supported options that have been added to this instance of Commander
     pub fn option_count(&self) -> usize {
@@ -96,87 +242,164 @@ impl<'a> Commander<'a> {
     }
 
     pub fn arg_count(&self) -> usize {
-        self.args.len()
+        match self.resolved_subcommand() {
+            Some(sub) => sub.arg_count(),
+            None => self.args.len(),
+        }
     }
 
     pub fn arguments(&'a self) -> impl Iterator<Item = (&'a String, &'a CmdArgument)> {
         self.args.iter()
     }
 
+    ///
+    /// Returns the positional ("free") arguments left over once every recognised option has
+    /// been consumed, in the order they appeared. Everything following a standalone `--` is
+    /// collected here verbatim, even if it looks like an option.
+    pub fn free(&self) -> &[String] {
+        match self.resolved_subcommand() {
+            Some(sub) => sub.free(),
+            None => &self.free,
+        }
+    }
+
     pub fn get_number_option(&self, option: &str, is_longform: bool) -> Option<i32> {
+        if let Some(sub) = self.resolved_subcommand() {
+            return sub.get_number_option(option, is_longform);
+        }
         if let Some(o) = self.get_supported_option(option, is_longform) {
             if let Some(arg) = self.args.get(o.shortform) {
-                match arg.value {
+                return match arg.value {
                     CmdArgumentValue::Number(v) => Some(v),
                     _ => None,
-                }
-            } else {
-                None
+                };
+            }
+            if let Some(CmdArgumentValue::Number(v)) = self.config.get(o.longform) {
+                return Some(*v);
             }
-        } else {
-            None
         }
+        None
     }
 
     pub fn get_float_option(&self, option: &str, is_longform: bool) -> Option<f32> {
+        if let Some(sub) = self.resolved_subcommand() {
+            return sub.get_float_option(option, is_longform);
+        }
         if let Some(o) = self.get_supported_option(option, is_longform) {
             if let Some(arg) = self.args.get(o.shortform) {
-                match arg.value {
+                return match arg.value {
                     CmdArgumentValue::Float(v) => Some(v),
                     _ => None,
-                }
-            } else {
-                None
+                };
+            }
+            if let Some(CmdArgumentValue::Float(v)) = self.config.get(o.longform) {
+                return Some(*v);
             }
-        } else {
-            None
         }
+        None
     }
 
     pub fn get_string_option(&self, option: &str, is_longform: bool) -> Option<String> {
+        if let Some(sub) = self.resolved_subcommand() {
+            return sub.get_string_option(option, is_longform);
+        }
         if let Some(o) = self.get_supported_option(option, is_longform) {
             if let Some(arg) = self.args.get(o.shortform) {
-                match &arg.value {
+                return match &arg.value {
                     CmdArgumentValue::String(v) => Some(v.clone()),
                     _ => None,
-                }
-            } else {
-                None
+                };
+            }
+            if let Some(CmdArgumentValue::String(v)) = self.config.get(o.longform) {
+                return Some(v.clone());
             }
-        } else {
-            None
         }
+        None
     }
 
     ///
-    /// Returns the path and filename of the calling executable of the current process
-    // pub fn executable(&'a self) -> &'a String {
-    //     // &self.args[0]
-    // }
+    /// Returns the value registered under `longform`, whether it came from a config file key
+    /// that has no matching CLI option, or (if `longform` does match a registered option) from
+    /// the config file fallback for that option. Explicit CLI args are not consulted here; use
+    /// `get_number_option`/`get_float_option`/`get_string_option` when CLI precedence matters.
+    pub fn get_value(&self, longform: &str) -> Option<&CmdArgumentValue> {
+        match self.resolved_subcommand() {
+            Some(sub) => sub.get_value(longform),
+            None => self.config.get(longform),
+        }
+    }
 
     ///
     /// Returns a string which contains a formatted output of available options and descriptions
     pub fn help(&self) -> String {
+        // when a subcommand is active, its own table is what the user actually wants to see
+        if let Some(sub) = self.resolved_subcommand() {
+            return sub.help();
+        }
+
         let mut output = String::from("Options available:\n");
 
         for option in self.options.iter() {
             output.push_str(&format!("\t--{}, -{}", option.longform, option.shortform));
             match option.value_type {
-                CmdOptionValueType::String => output.push_str(&format!("\t\t[string]")),
-                CmdOptionValueType::Float => output.push_str(&format!("\t\t[Float]")),
-                CmdOptionValueType::Number => output.push_str(&format!("\t\t[Number]")),
+                CmdOptionValueType::String => match option.allowed {
+                    Some(values) => output.push_str(&format!("\t\t[string: {}]", values.join("|"))),
+                    None => output.push_str("\t\t[string]"),
+                },
+                CmdOptionValueType::Float => output.push_str("\t\t[Float]"),
+                CmdOptionValueType::Number => output.push_str("\t\t[Number]"),
                 CmdOptionValueType::NoValue => output.push_str("\t\t[no paramater]")
             }
-            output.push_str(&format!("\t\t{}\n", 
-                        option.description.to_string()));
+            output.push_str(&format!("\t\t{}\n", option.description));
+        }
+
+        if !self.subcommands.is_empty() {
+            output.push_str("\nSubcommands:\n");
+            for (name, description, _) in self.subcommands.iter() {
+                output.push_str(&format!("\t{}\t\t{}\n", name, description));
+            }
         }
 
         output
     }
-    
+
+    ///
+    /// Loads a config file and merges its values in as fallbacks for any options not supplied
+    /// on the command line. The file format is chosen from its extension (`.json` requires the
+    /// `config_json` feature, `.toml` requires `config_toml`). Values are coerced into the
+    /// `CmdOptionValueType` registered for a matching longform option; a value that doesn't fit
+    /// that type produces a `ConfigError::TypeMismatch` rather than being silently dropped. Keys
+    /// with no matching option are kept as-is, retrievable later via `get_value`.
+    ///
+    /// Explicit command line arguments always take precedence over config file values.
+    #[cfg(any(feature = "config_json", feature = "config_toml"))]
+    pub fn load_config<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| ConfigError::Io(e.to_string()))?;
+
+        let map = match path.extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "config_json")]
+            Some("json") => self.parse_json_config(&contents)?,
+            #[cfg(feature = "config_toml")]
+            Some("toml") => self.parse_toml_config(&contents)?,
+            Some(ext) => return Err(ConfigError::UnsupportedFormat(ext.to_string())),
+            None => return Err(ConfigError::UnsupportedFormat(String::from("<none>"))),
+        };
+
+        self.config = map;
+        Ok(())
+    }
+
+    /// No config-file format is compiled in without the `config_json`/`config_toml` features, so
+    /// every path is unsupported.
+    #[cfg(not(any(feature = "config_json", feature = "config_toml")))]
+    pub fn load_config<P: AsRef<Path>>(&mut self, _path: P) -> Result<(), ConfigError> {
+        Err(ConfigError::UnsupportedFormat(String::from("<none>")))
+    }
+
     // PRIVATE
     // Extracts element 0 of the arguments and adds it to the Arguments map under a predefined special key '__exec__'
-    fn add_executable_arg(&mut self, args: &Vec<String>) {
+    fn add_executable_arg(&mut self, args: &[String]) {
         // store the first element as the process launch executable
         self.args.insert(String::from("__exec__"), CmdArgument { option: "__exec__".to_string(), value: CmdArgumentValue::String(args[0].clone())});
     }
@@ -185,68 +408,323 @@ impl<'a> Commander<'a> {
     // Parses the command line arguemnts and matches them to the valid registered options
     // in the object. If a match is found, the argument is stored and the value parsed according 
     // to the type specified when the option was added to the Commander
-    fn parse_args(&mut self, args: Vec<String>) {
+    fn parse_args(&mut self, args: Vec<String>) -> Result<(), CmdError> {
         let mut current_arg: CmdArgument;
         let mut iter = args.iter().skip(1);
+        let mut free_only = false;
+        // only the first non-flag token is eligible to route to a subcommand; once we've seen
+        // one, later non-flag tokens are always free arguments.
+        let mut seen_positional = false;
 
-        // self.parse_args(&iter);
         while let Some(arg) = iter.next() {
-            let shortform = arg.starts_with("-");
+            if free_only {
+                self.free.push(arg.clone());
+                continue;
+            }
+
+            if arg == "--" {
+                free_only = true;
+                continue;
+            }
+
+            let shortform = !arg.starts_with("--") && arg.starts_with("-");
             let longform = arg.starts_with("--");
-            let value = {
-                if longform {
-                    &arg[2..]
-                } else if shortform {
-                    &arg[1..]
-                } else {
-                    arg
+
+            if !longform && !shortform {
+                if !seen_positional {
+                    if let Some(idx) = self.subcommands.iter().position(|(name, _, _)| *name == arg.as_str()) {
+                        self.active_subcommand = Some(self.subcommands[idx].0.to_string());
+
+                        let mut sub_args = vec![args[0].clone()];
+                        sub_args.extend(iter.cloned());
+
+                        let sub = &mut self.subcommands[idx].2;
+                        sub.add_executable_arg(&sub_args);
+                        return sub.parse_args(sub_args);
+                    }
+                }
+
+                seen_positional = true;
+                self.free.push(arg.clone());
+                continue;
+            }
+
+            // an attached value is one supplied on the same argv element as the option, either
+            // after an '=' (longform) or immediately following the flag characters (shortform),
+            // e.g. `--input=foo.txt` or `-c10`. When present, it is used instead of consuming
+            // the next argv element.
+            let (option, inline_value) = if longform {
+                let body = &arg[2..];
+                let (name, inline_value) = match body.find('=') {
+                    Some(pos) => (&body[..pos], Some(&body[pos + 1..])),
+                    None => (body, None),
+                };
+                match self.get_supported_option(name, true) {
+                    Some(option) => (option, inline_value),
+                    None => return Err(CmdError::UnknownOption(name.to_string())),
+                }
+            } else {
+                match self.match_short_option(&arg[1..]) {
+                    Some(m) => m,
+                    None => return Err(CmdError::UnknownOption(arg.to_string())),
                 }
             };
 
-            if longform || shortform {
-                let o = self.get_supported_option(value, longform);
-                match o {
-                    Some(option) => {
-                        current_arg = CmdArgument {
-                            option: option.shortform.to_string(),
-                            value: CmdArgumentValue::NoValue,
-                        };
-
-                        // if we are expecting a value in the next element...
-                        if option.value_type != CmdOptionValueType::NoValue {
-                            if let Some(v) = iter.next() {
-                                current_arg.value = match option.value_type {
-                                    CmdOptionValueType::String => CmdArgumentValue::String(v.to_string()),
-                                    CmdOptionValueType::Number => CmdArgumentValue::Number(v.parse().unwrap()),
-                                    CmdOptionValueType::Float => CmdArgumentValue::Float(v.parse().unwrap()),
-                                    _ => unreachable!(),
-                                }
+            current_arg = CmdArgument {
+                option: option.shortform.to_string(),
+                value: CmdArgumentValue::NoValue,
+            };
+
+            // if we are expecting a value, either take it from the same argv element or
+            // consume the next one...
+            if option.value_type != CmdOptionValueType::NoValue {
+                let raw = match inline_value {
+                    Some(v) => Some(v.to_string()),
+                    None => iter.next().cloned(),
+                };
+                let raw = match raw {
+                    Some(v) => v,
+                    None => return Err(CmdError::MissingValue(option.longform.to_string())),
+                };
+
+                current_arg.value = match option.value_type {
+                    CmdOptionValueType::String => {
+                        if let Some(allowed) = option.allowed {
+                            if !allowed.contains(&raw.as_str()) {
+                                return Err(CmdError::InvalidValue {
+                                    option: option.longform.to_string(),
+                                    expected: CmdOptionValueType::String,
+                                    got: raw,
+                                    allowed: Some(allowed.iter().map(|v| v.to_string()).collect()),
+                                });
                             }
                         }
-
-                        self.args.insert(option.shortform.to_string(), CmdArgument { 
-                            option: current_arg.option,
-                            value: current_arg.value,
-                        });
+                        CmdArgumentValue::String(raw)
                     },
-                    None => eprintln!("[BAD] O({}): {}", if longform { "L" } else { "S" }, value),
-                } 
-            } else {
-                eprintln!("[BAD?] V: {}", value);
+                    CmdOptionValueType::Number => match raw.parse() {
+                        Ok(v) => CmdArgumentValue::Number(v),
+                        Err(_) => return Err(CmdError::InvalidValue {
+                            option: option.longform.to_string(),
+                            expected: CmdOptionValueType::Number,
+                            got: raw,
+                            allowed: None,
+                        }),
+                    },
+                    CmdOptionValueType::Float => match raw.parse() {
+                        Ok(v) => CmdArgumentValue::Float(v),
+                        Err(_) => return Err(CmdError::InvalidValue {
+                            option: option.longform.to_string(),
+                            expected: CmdOptionValueType::Float,
+                            got: raw,
+                            allowed: None,
+                        }),
+                    },
+                    CmdOptionValueType::NoValue => unreachable!(),
+                };
             }
+
+            self.args.insert(option.shortform.to_string(), CmdArgument {
+                option: current_arg.option,
+                value: current_arg.value,
+            });
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Checks that every option registered via `add_required_option` ended up in `self.args` or
+    /// `self.config`, matching the promise in `add_required_option`'s doc comment that a required
+    /// option can be satisfied either on the command line or via a loaded config file. Call this
+    /// once parsing and any `load_config` call have both happened; validating any earlier would
+    /// reject options that a not-yet-loaded config file was going to supply.
+    ///
+    /// If a subcommand was routed into, its required options are checked too, alongside this
+    /// Commander's own.
+    pub fn validate_required(&self) -> Result<(), CmdError> {
+        let mut missing: Vec<String> = self.options.iter()
+            .filter(|o| o.required
+                && !self.args.contains_key(o.shortform)
+                && !self.config.contains_key(o.longform))
+            .map(|o| o.longform.to_string())
+            .collect();
+
+        if let Some(sub) = self.resolved_subcommand() {
+            if let Err(CmdError::MissingRequired(sub_missing)) = sub.validate_required() {
+                missing.extend(sub_missing);
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(CmdError::MissingRequired(missing))
+        }
+    }
+
+    // PRIVATE
+    // Parses JSON config file contents into a map of longform -> CmdArgumentValue, coercing
+    // each value into the type registered for a matching option (or inferring one for keys
+    // that don't match any registered option).
+    #[cfg(feature = "config_json")]
+    fn parse_json_config(&self, contents: &str) -> Result<HashMap<String, CmdArgumentValue>, ConfigError> {
+        let root: serde_json::Value = serde_json::from_str(contents)
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+        let object = root.as_object()
+            .ok_or_else(|| ConfigError::Parse(String::from("config root must be an object")))?;
+
+        let mut map = HashMap::new();
+        for (key, value) in object {
+            let option = self.get_supported_option(key, true);
+            map.insert(key.clone(), Self::coerce_json_value(key, option, value)?);
+        }
+
+        Ok(map)
+    }
+
+    // PRIVATE
+    #[cfg(feature = "config_json")]
+    fn coerce_json_value(key: &str, option: Option<&CmdLineOption>, value: &serde_json::Value) -> Result<CmdArgumentValue, ConfigError> {
+        let expected = option.map(|o| o.value_type);
+        match (expected, value) {
+            (Some(CmdOptionValueType::String), serde_json::Value::String(s)) => {
+                if let Some(allowed) = option.and_then(|o| o.allowed) {
+                    if !allowed.contains(&s.as_str()) {
+                        return Err(ConfigError::InvalidValue {
+                            option: key.to_string(),
+                            allowed: allowed.iter().map(|v| v.to_string()).collect(),
+                            got: s.clone(),
+                        });
+                    }
+                }
+                Ok(CmdArgumentValue::String(s.clone()))
+            },
+            (Some(CmdOptionValueType::Number), serde_json::Value::Number(n)) if n.is_i64() =>
+                Ok(CmdArgumentValue::Number(n.as_i64().unwrap() as i32)),
+            (Some(CmdOptionValueType::Float), serde_json::Value::Number(n)) =>
+                Ok(CmdArgumentValue::Float(n.as_f64().unwrap() as f32)),
+            (Some(expected), other) => Err(ConfigError::TypeMismatch {
+                option: key.to_string(),
+                expected,
+                got: Self::json_type_name(other).to_string(),
+            }),
+            (None, serde_json::Value::String(s)) => Ok(CmdArgumentValue::String(s.clone())),
+            (None, serde_json::Value::Number(n)) if n.is_i64() => Ok(CmdArgumentValue::Number(n.as_i64().unwrap() as i32)),
+            (None, serde_json::Value::Number(n)) => Ok(CmdArgumentValue::Float(n.as_f64().unwrap() as f32)),
+            (None, other) => Err(ConfigError::Parse(format!("unsupported value for '{}': {}", key, Self::json_type_name(other)))),
+        }
+    }
+
+    // PRIVATE
+    #[cfg(feature = "config_json")]
+    fn json_type_name(value: &serde_json::Value) -> &'static str {
+        match value {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "bool",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        }
+    }
+
+    // PRIVATE
+    // Parses TOML config file contents the same way parse_json_config does for JSON.
+    #[cfg(feature = "config_toml")]
+    fn parse_toml_config(&self, contents: &str) -> Result<HashMap<String, CmdArgumentValue>, ConfigError> {
+        let root: toml::Value = contents.parse()
+            .map_err(|e: toml::de::Error| ConfigError::Parse(e.to_string()))?;
+        let table = root.as_table()
+            .ok_or_else(|| ConfigError::Parse(String::from("config root must be a table")))?;
+
+        let mut map = HashMap::new();
+        for (key, value) in table {
+            let option = self.get_supported_option(key, true);
+            map.insert(key.clone(), Self::coerce_toml_value(key, option, value)?);
+        }
+
+        Ok(map)
+    }
+
+    // PRIVATE
+    #[cfg(feature = "config_toml")]
+    fn coerce_toml_value(key: &str, option: Option<&CmdLineOption>, value: &toml::Value) -> Result<CmdArgumentValue, ConfigError> {
+        let expected = option.map(|o| o.value_type);
+        match (expected, value) {
+            (Some(CmdOptionValueType::String), toml::Value::String(s)) => {
+                if let Some(allowed) = option.and_then(|o| o.allowed) {
+                    if !allowed.contains(&s.as_str()) {
+                        return Err(ConfigError::InvalidValue {
+                            option: key.to_string(),
+                            allowed: allowed.iter().map(|v| v.to_string()).collect(),
+                            got: s.clone(),
+                        });
+                    }
+                }
+                Ok(CmdArgumentValue::String(s.clone()))
+            },
+            (Some(CmdOptionValueType::Number), toml::Value::Integer(n)) => Ok(CmdArgumentValue::Number(*n as i32)),
+            (Some(CmdOptionValueType::Float), toml::Value::Float(n)) => Ok(CmdArgumentValue::Float(*n as f32)),
+            (Some(CmdOptionValueType::Float), toml::Value::Integer(n)) => Ok(CmdArgumentValue::Float(*n as f32)),
+            (Some(expected), other) => Err(ConfigError::TypeMismatch {
+                option: key.to_string(),
+                expected,
+                got: Self::toml_type_name(other).to_string(),
+            }),
+            (None, toml::Value::String(s)) => Ok(CmdArgumentValue::String(s.clone())),
+            (None, toml::Value::Integer(n)) => Ok(CmdArgumentValue::Number(*n as i32)),
+            (None, toml::Value::Float(n)) => Ok(CmdArgumentValue::Float(*n as f32)),
+            (None, other) => Err(ConfigError::Parse(format!("unsupported value for '{}': {}", key, Self::toml_type_name(other)))),
+        }
+    }
+
+    // PRIVATE
+    #[cfg(feature = "config_toml")]
+    fn toml_type_name(value: &toml::Value) -> &'static str {
+        match value {
+            toml::Value::String(_) => "string",
+            toml::Value::Integer(_) => "integer",
+            toml::Value::Float(_) => "float",
+            toml::Value::Boolean(_) => "bool",
+            toml::Value::Datetime(_) => "datetime",
+            toml::Value::Array(_) => "array",
+            toml::Value::Table(_) => "table",
         }
     }
 
     //
     // PRIVATE
     // Checks if the provided option is supported by this instance of Commander
-    fn get_supported_option(&self, option: &'a str, is_longform: bool) -> Option<&'a CmdLineOption> {
+    #[allow(mismatched_lifetime_syntaxes)]
+    fn get_supported_option(&self, option: &'a str, is_longform: bool) -> Option<&'a CmdLineOption<'_>> {
         let result = self.options.iter().find(|o| {
             (!is_longform && o.shortform == option) || (is_longform && o.longform == option)
         });
 
         result
     }
+
+    // PRIVATE
+    // Finds the registered shortform that is a prefix of `text` (the argv element with its
+    // leading '-' stripped), returning the option along with whatever follows the flag. The
+    // longest matching shortform wins, since shortforms here aren't limited to a single
+    // character (e.g. "if" for --input). A NoValue option only matches when it consumes the
+    // whole of `text`; an option that takes a value matches with any trailing characters, which
+    // become its attached value (e.g. "c10" for "-c 10").
+    #[allow(mismatched_lifetime_syntaxes)]
+    fn match_short_option(&'a self, text: &'a str) -> Option<(&'a CmdLineOption<'_>, Option<&'a str>)> {
+        self.options.iter()
+            .filter(|o| text.starts_with(o.shortform))
+            .filter(|o| o.value_type != CmdOptionValueType::NoValue || text.len() == o.shortform.len())
+            .max_by_key(|o| o.shortform.len())
+            .map(|o| {
+                let attached = &text[o.shortform.len()..];
+                // shortform options have no "=" syntax, so an empty remainder (e.g. "-c" alone)
+                // always means "no attached value", never an explicit empty one.
+                let inline_value = if attached.is_empty() { None } else { Some(attached) };
+                (o, inline_value)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -275,7 +753,7 @@ mod tests {
         let args = vec!["test_executable".to_string(), "-c".to_string(), "10".to_string()];
         cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number);
         cmd.add_executable_arg(&args);
-        cmd.parse_args(args);
+        cmd.parse_args(args).unwrap();
         assert_eq!(10, cmd.get_number_option("c", false).unwrap());
     }
 
@@ -285,7 +763,7 @@ mod tests {
         let args = vec!["test_executable".to_string(), "-b".to_string(), "0.10".to_string()];
         cmd.add_option("b", "balance", "Balance amount", CmdOptionValueType::Float);
         cmd.add_executable_arg(&args);
-        cmd.parse_args(args);
+        cmd.parse_args(args).unwrap();
         assert_eq!(0.10, cmd.get_float_option("b", false).unwrap());
     }
 
@@ -295,10 +773,330 @@ mod tests {
         let args = vec!["test_executable".to_string(), "-f".to_string(), "textfile.txt".to_string()];
         cmd.add_option("f", "file", "File name", CmdOptionValueType::String);
         cmd.add_executable_arg(&args);
-        cmd.parse_args(args);
+        cmd.parse_args(args).unwrap();
         assert_eq!("textfile.txt", cmd.get_string_option("f", false).unwrap());
     }
 
+    #[test]
+    fn test_subcommand_routes_args_and_getters_delegate() {
+        let mut cmd = Commander::new();
+        cmd.add_option("v", "verbose", "Be verbose", CmdOptionValueType::NoValue);
+        cmd.add_subcommand("add", "Add a file to the index")
+            .add_option("p", "patch", "Interactively choose hunks", CmdOptionValueType::NoValue);
+
+        let args = vec!["test_executable".to_string(), "add".to_string(), "-p".to_string(), "file.txt".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        assert_eq!(Some("add"), cmd.active_subcommand());
+        assert!(cmd.arg_count() >= 1);
+        assert_eq!(&["file.txt".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_non_matching_positional_is_not_treated_as_subcommand() {
+        let mut cmd = Commander::new();
+        cmd.add_subcommand("add", "Add a file to the index");
+
+        let args = vec!["test_executable".to_string(), "status".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        assert_eq!(None, cmd.active_subcommand());
+        assert_eq!(&["status".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_subcommand_name_after_first_positional_is_not_routed() {
+        let mut cmd = Commander::new();
+        cmd.add_subcommand("add", "Add a file to the index");
+
+        let args = vec!["test_executable".to_string(), "file1".to_string(), "add".to_string(), "extra".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        assert_eq!(None, cmd.active_subcommand());
+        assert_eq!(&["file1".to_string(), "add".to_string(), "extra".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_free_arguments_are_collected() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--verbose".to_string(), "file1".to_string(), "file2".to_string()];
+        cmd.add_option("v", "verbose", "Be verbose", CmdOptionValueType::NoValue);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!(&["file1".to_string(), "file2".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_double_dash_terminator_collects_everything_after_it() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--".to_string(), "--not-an-option".to_string(), "-x".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!(&["--not-an-option".to_string(), "-x".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_missing_required_option_is_an_error() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string()];
+        cmd.add_required_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        match cmd.validate_required() {
+            Err(CmdError::MissingRequired(missing)) => assert_eq!(vec!["input".to_string()], missing),
+            other => panic!("expected MissingRequired, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_required_option_present_parses_ok() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--input=foo.txt".to_string()];
+        cmd.add_required_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        cmd.validate_required().unwrap();
+        assert_eq!("foo.txt", cmd.get_string_option("input", true).unwrap());
+    }
+
+    #[test]
+    fn test_required_option_satisfied_via_config_only() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string()];
+        cmd.add_required_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.config.insert("input".to_string(), CmdArgumentValue::String("config.txt".to_string()));
+        cmd.parse_args(args).unwrap();
+        cmd.validate_required().unwrap();
+        assert_eq!("config.txt", cmd.get_string_option("input", true).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "config_json")]
+    fn test_required_option_satisfied_via_real_load_config() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string()];
+        cmd.add_required_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        // init()/parse_args() alone can't know a config file is coming, so validating here
+        // would be premature and must fail...
+        match cmd.validate_required() {
+            Err(CmdError::MissingRequired(missing)) => assert_eq!(vec!["input".to_string()], missing),
+            other => panic!("expected MissingRequired, got {:?}", other),
+        }
+
+        let path = std::env::temp_dir().join(format!("commander_test_config_{}.json", std::process::id()));
+        fs::write(&path, r#"{"input": "config.txt"}"#).unwrap();
+        let result = cmd.load_config(&path);
+        fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        // ...but once the config file has been loaded, the same required option is satisfied.
+        cmd.validate_required().unwrap();
+        assert_eq!("config.txt", cmd.get_string_option("input", true).unwrap());
+    }
+
+    #[test]
+    fn test_unknown_option_is_an_error() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--bogus".to_string()];
+        cmd.add_option("v", "version", "Show the version", CmdOptionValueType::NoValue);
+        cmd.add_executable_arg(&args);
+        match cmd.parse_args(args) {
+            Err(CmdError::UnknownOption(opt)) => assert_eq!("bogus", opt),
+            other => panic!("expected UnknownOption, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_allowed_value_is_accepted() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--format=toml".to_string()];
+        cmd.add_option_with_values("f", "format", "Output format", &["json", "toml", "text"]);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!("toml", cmd.get_string_option("format", true).unwrap());
+    }
+
+    #[test]
+    fn test_disallowed_value_is_an_error() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--format=xml".to_string()];
+        cmd.add_option_with_values("f", "format", "Output format", &["json", "toml", "text"]);
+        cmd.add_executable_arg(&args);
+        match cmd.parse_args(args) {
+            Err(CmdError::InvalidValue { option, got, allowed, .. }) => {
+                assert_eq!("format", option);
+                assert_eq!("xml", got);
+                assert_eq!(Some(vec!["json".to_string(), "toml".to_string(), "text".to_string()]), allowed);
+            },
+            other => panic!("expected InvalidValue, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_number_value_is_an_error() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "-c".to_string(), "notanumber".to_string()];
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number);
+        cmd.add_executable_arg(&args);
+        match cmd.parse_args(args) {
+            Err(CmdError::InvalidValue { option, expected, got, allowed }) => {
+                assert_eq!("count", option);
+                assert_eq!(CmdOptionValueType::Number, expected);
+                assert_eq!("notanumber", got);
+                assert_eq!(None, allowed);
+            },
+            other => panic!("expected InvalidValue, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_longform_equals_value() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--input=foo.txt".to_string()];
+        cmd.add_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!("foo.txt", cmd.get_string_option("input", true).unwrap());
+    }
+
+    #[test]
+    fn test_longform_equals_empty_value_is_not_swallowed() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "--input=".to_string(), "file.txt".to_string()];
+        cmd.add_option("if", "input", "File to use as input", CmdOptionValueType::String);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!("", cmd.get_string_option("input", true).unwrap());
+        assert_eq!(&["file.txt".to_string()], cmd.free());
+    }
+
+    #[test]
+    fn test_attached_short_value() {
+        let mut cmd = Commander::new();
+        let args = vec!["test_executable".to_string(), "-c10".to_string()];
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number);
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+        assert_eq!(10, cmd.get_number_option("c", false).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "config_json")]
+    fn test_config_json_fallback_and_precedence() {
+        let mut cmd = Commander::new();
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number)
+            .add_option("f", "file", "File name", CmdOptionValueType::String);
+
+        let args = vec!["test_executable".to_string(), "-f".to_string(), "cli.txt".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        cmd.config = cmd.parse_json_config(r#"{"count": 5, "file": "config.txt", "region": "eu"}"#).unwrap();
+
+        assert_eq!(5, cmd.get_number_option("c", false).unwrap());
+        assert_eq!("cli.txt", cmd.get_string_option("f", false).unwrap());
+        match cmd.get_value("region") {
+            Some(CmdArgumentValue::String(v)) => assert_eq!("eu", v),
+            _ => panic!("expected a string value for 'region'"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "config_json")]
+    fn test_config_json_type_mismatch() {
+        let mut cmd = Commander::new();
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number);
+
+        let err = cmd.parse_json_config(r#"{"count": "not a number"}"#).unwrap_err();
+        match err {
+            ConfigError::TypeMismatch { option, expected, got } => {
+                assert_eq!("count", option);
+                assert_eq!(CmdOptionValueType::Number, expected);
+                assert_eq!("string", got);
+            },
+            _ => panic!("expected a TypeMismatch error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "config_json")]
+    fn test_config_json_rejects_disallowed_value() {
+        let mut cmd = Commander::new();
+        cmd.add_option_with_values("f", "format", "Output format", &["json", "toml", "text"]);
+
+        let err = cmd.parse_json_config(r#"{"format": "xml"}"#).unwrap_err();
+        match err {
+            ConfigError::InvalidValue { option, allowed, got } => {
+                assert_eq!("format", option);
+                assert_eq!(vec!["json".to_string(), "toml".to_string(), "text".to_string()], allowed);
+                assert_eq!("xml", got);
+            },
+            _ => panic!("expected an InvalidValue error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "config_toml")]
+    fn test_config_toml_fallback_and_precedence() {
+        let mut cmd = Commander::new();
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number)
+            .add_option("f", "file", "File name", CmdOptionValueType::String);
+
+        let args = vec!["test_executable".to_string(), "-f".to_string(), "cli.txt".to_string()];
+        cmd.add_executable_arg(&args);
+        cmd.parse_args(args).unwrap();
+
+        cmd.config = cmd.parse_toml_config("count = 5\nfile = \"config.txt\"\nregion = \"eu\"\n").unwrap();
+
+        assert_eq!(5, cmd.get_number_option("c", false).unwrap());
+        assert_eq!("cli.txt", cmd.get_string_option("f", false).unwrap());
+        match cmd.get_value("region") {
+            Some(CmdArgumentValue::String(v)) => assert_eq!("eu", v),
+            _ => panic!("expected a string value for 'region'"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "config_toml")]
+    fn test_config_toml_type_mismatch() {
+        let mut cmd = Commander::new();
+        cmd.add_option("c", "count", "Number of iterations", CmdOptionValueType::Number);
+
+        let err = cmd.parse_toml_config("count = \"not a number\"\n").unwrap_err();
+        match err {
+            ConfigError::TypeMismatch { option, expected, got } => {
+                assert_eq!("count", option);
+                assert_eq!(CmdOptionValueType::Number, expected);
+                assert_eq!("string", got);
+            },
+            _ => panic!("expected a TypeMismatch error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "config_toml")]
+    fn test_config_toml_rejects_disallowed_value() {
+        let mut cmd = Commander::new();
+        cmd.add_option_with_values("f", "format", "Output format", &["json", "toml", "text"]);
+
+        let err = cmd.parse_toml_config("format = \"xml\"\n").unwrap_err();
+        match err {
+            ConfigError::InvalidValue { option, allowed, got } => {
+                assert_eq!("format", option);
+                assert_eq!(vec!["json".to_string(), "toml".to_string(), "text".to_string()], allowed);
+                assert_eq!("xml", got);
+            },
+            _ => panic!("expected an InvalidValue error"),
+        }
+    }
+
     #[test]
     #[ignore]
     fn test_help() {
@@ -8,9 +8,12 @@ fn main() {
         .add_option("h", "help", "Show this help", CmdOptionValueType::NoValue)
         .add_option("if", "input", "File to use as input", CmdOptionValueType::String)
         .add_option("c", "count", "Amount of times to do something", CmdOptionValueType::Number)
-        .add_option("b", "balance", "Amount of money in your bank account", CmdOptionValueType::Float)
-        .init()
-        ;
+        .add_option("b", "balance", "Amount of money in your bank account", CmdOptionValueType::Float);
+
+    if let Err(e) = cmd.init() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 
     if cmd.arg_count() == 1 {
         println!("{}", cmd.help());